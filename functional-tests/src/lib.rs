@@ -22,6 +22,11 @@ mod tests {
     use tempdir::TempDir;
     const SOPS_BINARY_PATH: &'static str = "./sops";
     const KMS_KEY: &'static str = "FUNCTIONAL_TEST_KMS_ARN";
+    // A throwaway age X25519 keypair used to exercise the native age backend.
+    const AGE_RECIPIENT: &'static str =
+        "age18xqdnpf69jeas4etpfzt8mtzpxy7ylf94msm6w8603tjchy6453qcgzsht";
+    const AGE_SECRET_KEY: &'static str =
+        "AGE-SECRET-KEY-17QNQDF45L7K323C64SV07C6WLSS3LWZSW8HRP3SNVN6NTSF7RF5QFDS6RK";
 
     macro_rules! assert_encrypted {
         ($object:expr, $key:expr) => {
@@ -681,6 +686,231 @@ b: ba"#
         assert!(String::from_utf8_lossy(&output.stdout).contains("secret"));
     }
 
+    #[test]
+    fn roundtrip_age() {
+        // Encrypt to an age recipient, then decrypt using the matching identity
+        // supplied through SOPS_AGE_KEY. No KMS or GPG keyring is involved.
+        let file_path = prepare_temp_file("test_roundtrip_age.yaml", "a: secret".as_bytes());
+        let output = Command::new(SOPS_BINARY_PATH)
+            .arg("encrypt")
+            .arg("--age")
+            .arg(AGE_RECIPIENT)
+            .arg("-i")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to encrypt a file with an age recipient"
+        );
+        let output = Command::new(SOPS_BINARY_PATH)
+            .env("SOPS_AGE_KEY", AGE_SECRET_KEY)
+            .arg("decrypt")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to decrypt a file encrypted to an age recipient"
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains("secret"));
+    }
+
+    #[test]
+    fn roundtrip_passphrase() {
+        // A passphrase master key wraps the data key with an Argon2id-derived
+        // key, so a file can be encrypted and decrypted with nothing but a
+        // secret string read from SOPS_PASSPHRASE.
+        let file_path =
+            prepare_temp_file("test_roundtrip_passphrase.yaml", "a: secret".as_bytes());
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .env("SOPS_PASSPHRASE", "correct horse battery staple")
+                .arg("encrypt")
+                .arg("--passphrase")
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to encrypt a file with a passphrase"
+        );
+        let output = Command::new(SOPS_BINARY_PATH)
+            .env("SOPS_PASSPHRASE", "correct horse battery staple")
+            .arg("decrypt")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to decrypt a passphrase-wrapped file"
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains("secret"));
+    }
+
+    #[test]
+    fn roundtrip_passphrase_wrong_passphrase() {
+        // The wrong passphrase must fail to unwrap the data key.
+        let file_path = prepare_temp_file(
+            "test_roundtrip_passphrase_wrong.yaml",
+            "a: secret".as_bytes(),
+        );
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .env("SOPS_PASSPHRASE", "correct horse battery staple")
+                .arg("encrypt")
+                .arg("--passphrase")
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to encrypt a file with a passphrase"
+        );
+        assert!(
+            !Command::new(SOPS_BINARY_PATH)
+                .env("SOPS_PASSPHRASE", "Tr0ub4dor&3")
+                .arg("decrypt")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS decrypted a passphrase-wrapped file with the wrong passphrase"
+        );
+    }
+
+    #[test]
+    fn roundtrip_age_missing_identity() {
+        // Without an identity in the environment, decryption must fail rather
+        // than silently returning ciphertext.
+        let file_path =
+            prepare_temp_file("test_roundtrip_age_missing.yaml", "a: secret".as_bytes());
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .arg("encrypt")
+                .arg("--age")
+                .arg(AGE_RECIPIENT)
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to encrypt a file with an age recipient"
+        );
+        assert!(
+            !Command::new(SOPS_BINARY_PATH)
+                .env_remove("SOPS_AGE_KEY")
+                .env_remove("SOPS_AGE_KEY_FILE")
+                .arg("decrypt")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS decrypted an age file without any identity available"
+        );
+    }
+
+    #[test]
+    fn decrypt_with_any_single_backend() {
+        // A file wrapped to both a PGP fingerprint and an age recipient must
+        // carry a stanza for each backend, and must decrypt when only one of the
+        // corresponding identities is available. (The CLI can't observe the
+        // concurrent racing inside the decrypt driver, so this asserts the
+        // first-success-wins *outcome* with the age identity as the only one
+        // present; a PGP-only decrypt is not exercised here.)
+        let fingerprint = env::var("FUNCTIONAL_TEST_PGP_FP")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_FP env var to be set");
+        let file_path =
+            prepare_temp_file("test_decrypt_any_backend.yaml", "a: secret".as_bytes());
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .arg("encrypt")
+                .arg("--pgp")
+                .arg(&fingerprint)
+                .arg("--age")
+                .arg(AGE_RECIPIENT)
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to encrypt to multiple backends"
+        );
+
+        // Both stanzas must be present in the metadata.
+        let mut encrypted = String::new();
+        File::open(&file_path)
+            .unwrap()
+            .read_to_string(&mut encrypted)
+            .unwrap();
+        let data: Value = serde_yaml::from_str(&encrypted).expect("Error parsing sops output");
+        let meta = match data {
+            Value::Mapping(ref m) => m
+                .get(&Value::String("sops".to_owned()))
+                .expect("sops metadata branch not found")
+                .clone(),
+            _ => panic!("sops output is not a mapping"),
+        };
+        if let Value::Mapping(meta) = meta {
+            assert!(
+                meta.get(&Value::String("pgp".to_owned())).is_some(),
+                "pgp stanza missing"
+            );
+            assert!(
+                meta.get(&Value::String("age".to_owned())).is_some(),
+                "age stanza missing"
+            );
+        } else {
+            panic!("sops metadata is not a mapping");
+        }
+
+        // Point GNUPGHOME at an empty directory so the ambient keyring/agent
+        // can't silently supply the PGP secret: the only way to deny a backend
+        // is to leave it without any usable identity, and merely unsetting
+        // $SOPS_PGP_SECRET_KEY doesn't -- the baseline PGP tests decrypt with
+        // no such env var because the key lives in the keyring. (This mirrors
+        // the isolation `roundtrip_pgp_in_process` relies on.)
+        let empty_gnupghome = TMP_DIR.path().join("empty-gnupghome-any-backend");
+        std::fs::create_dir_all(&empty_gnupghome).expect("Unable to create empty GNUPGHOME");
+
+        // Only the age identity is available -- decryption must still succeed.
+        let output = Command::new(SOPS_BINARY_PATH)
+            .env("SOPS_AGE_KEY", AGE_SECRET_KEY)
+            .env("GNUPGHOME", &empty_gnupghome)
+            .env_remove("SOPS_PGP_SECRET_KEY")
+            .arg("decrypt")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to decrypt when only the age identity was available"
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains("secret"));
+
+        // With neither identity available, decryption must fail.
+        assert!(
+            !Command::new(SOPS_BINARY_PATH)
+                .env("GNUPGHOME", &empty_gnupghome)
+                .env_remove("SOPS_AGE_KEY")
+                .env_remove("SOPS_AGE_KEY_FILE")
+                .env_remove("SOPS_PGP_SECRET_KEY")
+                .arg("decrypt")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS decrypted with no identity for any backend"
+        );
+    }
+
     #[test]
     fn roundtrip_shamir_missing_decryption_key() {
         // The .sops.yaml file ensures this file is encrypted with two key groups, each with one GPG key,
@@ -710,6 +940,115 @@ b: ba"#
         );
     }
 
+    #[test]
+    fn roundtrip_pgp_in_process() {
+        // The in-process sequoia backend must encrypt and decrypt without ever
+        // contacting a gpg binary or agent. The backend is selected explicitly
+        // with `--pgp-backend sequoia` (the same selector the armored-file test
+        // uses); pointing GNUPGHOME at an empty directory corroborates that no
+        // external keyring is consulted. The armored secret key is fed through
+        // the environment.
+        let secret_key = env::var("FUNCTIONAL_TEST_PGP_SECRET_KEY")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_SECRET_KEY env var to be set");
+        let fingerprint = env::var("FUNCTIONAL_TEST_PGP_FP")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_FP env var to be set");
+        let empty_gnupghome = TMP_DIR.path().join("empty-gnupghome");
+        std::fs::create_dir_all(&empty_gnupghome).expect("Unable to create empty GNUPGHOME");
+
+        let file_path = prepare_temp_file("test_roundtrip_pgp_sequoia.yaml", "a: secret".as_bytes());
+        let output = Command::new(SOPS_BINARY_PATH)
+            .env("GNUPGHOME", &empty_gnupghome)
+            .arg("encrypt")
+            .arg("--pgp-backend")
+            .arg("sequoia")
+            .arg("--pgp")
+            .arg(&fingerprint)
+            .arg("-i")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to encrypt with the in-process PGP backend"
+        );
+        let output = Command::new(SOPS_BINARY_PATH)
+            .env("GNUPGHOME", &empty_gnupghome)
+            .env("SOPS_PGP_SECRET_KEY", &secret_key)
+            .arg("decrypt")
+            .arg("--pgp-backend")
+            .arg("sequoia")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to decrypt with the in-process PGP backend"
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains("secret"));
+    }
+
+    #[test]
+    fn verbose_decrypt_failure_summary() {
+        // A failed decrypt run with --verbose must emit a structured summary
+        // naming each recipient that was tried and why it failed, while never
+        // leaking the data key or plaintext into the trace.
+        let file_path = prepare_temp_file(
+            "test_verbose_failure.yaml",
+            "a: topsecretvalue".as_bytes(),
+        );
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .arg("encrypt")
+                .arg("--age")
+                .arg(AGE_RECIPIENT)
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to encrypt a file"
+        );
+        let output = Command::new(SOPS_BINARY_PATH)
+            .env("SOPS_LOG", "debug")
+            .env_remove("SOPS_AGE_KEY")
+            .env_remove("SOPS_AGE_KEY_FILE")
+            .arg("decrypt")
+            .arg("--verbose")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            !output.status.success(),
+            "SOPS decrypted without any identity available"
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // The summary must name the backend and the exact recipient that was
+        // tried, not merely contain the three letters "age" somewhere.
+        assert!(
+            stderr.contains(AGE_RECIPIENT),
+            "Verbose trace did not name the age recipient that was tried"
+        );
+        // ...and it must record why that recipient failed.
+        let lower = stderr.to_lowercase();
+        assert!(
+            lower.contains("no identity")
+                || lower.contains("no key")
+                || lower.contains("failed"),
+            "Verbose trace did not explain why the recipient failed: {}",
+            stderr
+        );
+        // Neither the plaintext nor any encrypted value may appear.
+        assert!(
+            !stderr.contains("topsecretvalue"),
+            "Verbose trace leaked plaintext"
+        );
+        assert!(
+            !stderr.contains("ENC["),
+            "Verbose trace leaked an encrypted value"
+        );
+    }
+
     #[test]
     fn test_decrypt_file_multiple_keys() {
         let file_path = prepare_temp_file(
@@ -727,6 +1066,207 @@ b: ba"#
         );
     }
 
+    #[test]
+    fn sign_and_verify() {
+        // `sops sign` canonicalizes the encrypted document and emits a detached
+        // OpenPGP signature; `sops verify` checks it against a trusted cert
+        // without decrypting. This runs on an already-encrypted file.
+        let signer_key = env::var("FUNCTIONAL_TEST_PGP_SECRET_KEY")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_SECRET_KEY env var to be set");
+        let signer_cert = env::var("FUNCTIONAL_TEST_PGP_CERT")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_CERT env var to be set");
+        let fingerprint = env::var("FUNCTIONAL_TEST_PGP_FP")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_FP env var to be set");
+
+        let file_path = prepare_temp_file("test_sign.yaml", "a: secret".as_bytes());
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .arg("encrypt")
+                .arg("--pgp")
+                .arg(&fingerprint)
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to encrypt a file to sign"
+        );
+        let sig_path = Path::join(TMP_DIR.path(), "test_sign.yaml.sig");
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .env("SOPS_PGP_SECRET_KEY", &signer_key)
+                .arg("sign")
+                .arg("--output")
+                .arg(&sig_path)
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to produce a detached signature"
+        );
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .arg("verify")
+                .arg("--cert")
+                .arg(&signer_cert)
+                .arg("--signature")
+                .arg(&sig_path)
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to verify a valid signature"
+        );
+    }
+
+    #[test]
+    fn verify_rejects_tampered_file() {
+        // A signature over the original document must not verify once the
+        // encrypted file is altered, so `verify` exits nonzero.
+        let signer_key = env::var("FUNCTIONAL_TEST_PGP_SECRET_KEY")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_SECRET_KEY env var to be set");
+        let signer_cert = env::var("FUNCTIONAL_TEST_PGP_CERT")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_CERT env var to be set");
+        let fingerprint = env::var("FUNCTIONAL_TEST_PGP_FP")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_FP env var to be set");
+
+        let file_path = prepare_temp_file("test_verify_tampered.yaml", "a: secret".as_bytes());
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .arg("encrypt")
+                .arg("--pgp")
+                .arg(&fingerprint)
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to encrypt a file to sign"
+        );
+        let sig_path = Path::join(TMP_DIR.path(), "test_verify_tampered.yaml.sig");
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .env("SOPS_PGP_SECRET_KEY", &signer_key)
+                .arg("sign")
+                .arg("--output")
+                .arg(&sig_path)
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to produce a detached signature"
+        );
+        // Flip a byte inside an encrypted value. Appending trailing whitespace
+        // would be ignored by a verifier that canonicalizes the document, so we
+        // mutate the ciphertext itself: find an `ENC[` value and change one
+        // character of its payload to a different, valid-alphabet character.
+        {
+            let mut contents = String::new();
+            File::open(&file_path)
+                .expect("Could not open encrypted file for tampering")
+                .read_to_string(&mut contents)
+                .expect("Could not read encrypted file for tampering");
+            let enc = contents.find("ENC[").expect("No encrypted value to tamper with");
+            let payload = enc
+                + contents[enc..]
+                    .find("data:")
+                    .expect("Encrypted value has no data payload")
+                + "data:".len();
+            let target = contents[payload..]
+                .char_indices()
+                .find(|&(_, c)| c.is_ascii_alphanumeric())
+                .map(|(i, _)| payload + i)
+                .expect("No ciphertext character to tamper with");
+            let original = contents.as_bytes()[target];
+            let replacement = if original == b'A' { "B" } else { "A" };
+            contents.replace_range(target..target + 1, replacement);
+            File::create(&file_path)
+                .expect("Could not reopen encrypted file for tampering")
+                .write_all(contents.as_bytes())
+                .expect("Could not tamper with encrypted file");
+        }
+        assert!(
+            !Command::new(SOPS_BINARY_PATH)
+                .arg("verify")
+                .arg("--cert")
+                .arg(&signer_cert)
+                .arg("--signature")
+                .arg(&sig_path)
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS verified a tampered file"
+        );
+    }
+
+    #[test]
+    fn pgp_in_process_from_armored_file() {
+        // The pure-Rust backend loads armored key material from a file (no gpg
+        // subprocess) and writes the same fingerprint-indexed `pgp` metadata the
+        // shell-out path produces, so files stay cross-compatible with upstream.
+        let secret_key_file = env::var("FUNCTIONAL_TEST_PGP_SECRET_KEY_FILE")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_SECRET_KEY_FILE env var to be set");
+        let cert_file = env::var("FUNCTIONAL_TEST_PGP_CERT_FILE")
+            .expect("Expected $FUNCTIONAL_TEST_PGP_CERT_FILE env var to be set");
+
+        let file_path = prepare_temp_file("test_pgp_armored_file.yaml", "a: secret".as_bytes());
+        let output = Command::new(SOPS_BINARY_PATH)
+            .arg("encrypt")
+            .arg("--pgp-backend")
+            .arg("sequoia")
+            .arg("--pgp-public-key")
+            .arg(&cert_file)
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to encrypt with an armored cert file"
+        );
+        let data: Value = serde_yaml::from_slice(&output.stdout)
+            .expect("Error parsing sops's YAML output");
+        if let Value::Mapping(m) = data {
+            let meta = m
+                .get(&Value::String("sops".to_owned()))
+                .expect("sops metadata branch not found");
+            if let Value::Mapping(ref meta) = *meta {
+                assert!(
+                    meta.get(&Value::String("pgp".to_owned())).is_some(),
+                    "pgp metadata stanza not found"
+                );
+            } else {
+                panic!("sops metadata is not a mapping");
+            }
+        } else {
+            panic!("sops's YAML output is not a mapping");
+        }
+
+        // Write the ciphertext back out and decrypt it with the armored secret
+        // key, again with no gpg binary involved.
+        let enc_path = prepare_temp_file("test_pgp_armored_file.enc.yaml", &output.stdout);
+        let output = Command::new(SOPS_BINARY_PATH)
+            .arg("decrypt")
+            .arg("--pgp-backend")
+            .arg("sequoia")
+            .arg("--pgp-secret-key")
+            .arg(&secret_key_file)
+            .arg(enc_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to decrypt with an armored secret key file"
+        );
+        assert!(String::from_utf8_lossy(&output.stdout).contains("secret"));
+    }
+
     #[test]
     fn extract_string() {
         let file_path = prepare_temp_file(
@@ -778,6 +1318,149 @@ b: ba"#
         assert_eq!(output.stdout, data);
     }
 
+    #[test]
+    fn roundtrip_binary_streaming() {
+        // Streaming mode splits the plaintext into fixed-size segments, encrypts
+        // each independently with a nonce derived from a segment counter, and
+        // reassembles them on decrypt -- all in bounded memory. A payload larger
+        // than a single 64 KiB segment exercises the multi-segment path.
+        let data: Vec<u8> = (0..(256 * 1024)).map(|i| (i % 251) as u8).collect();
+        let file_path = prepare_temp_file("test_stream.binary", &data);
+        let output = Command::new(SOPS_BINARY_PATH)
+            .arg("encrypt")
+            .arg("--input-type")
+            .arg("binary")
+            .arg("--stream")
+            .arg("-i")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to stream-encrypt a large binary file"
+        );
+
+        // The chosen segment size is recorded in the metadata so decryption can
+        // reproduce the nonce sequence.
+        let mut encrypted = String::new();
+        File::open(&file_path)
+            .unwrap()
+            .read_to_string(&mut encrypted)
+            .unwrap();
+        assert!(
+            encrypted.contains("segment_size"),
+            "Streaming metadata did not record the segment size"
+        );
+
+        let output = Command::new(SOPS_BINARY_PATH)
+            .arg("decrypt")
+            .arg("--input-type")
+            .arg("binary")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            output.status.success(),
+            "SOPS failed to stream-decrypt a large binary file"
+        );
+        assert_eq!(output.stdout, data);
+    }
+
+    #[test]
+    fn streaming_detects_truncation() {
+        // Truncating the ciphertext stream must be detected: the authenticated
+        // total segment count no longer matches what is present.
+        let data: Vec<u8> = (0..(256 * 1024)).map(|i| (i % 251) as u8).collect();
+        let file_path = prepare_temp_file("test_stream_truncate.binary", &data);
+        assert!(
+            Command::new(SOPS_BINARY_PATH)
+                .arg("encrypt")
+                .arg("--input-type")
+                .arg("binary")
+                .arg("--stream")
+                .arg("-i")
+                .arg(file_path.clone())
+                .output()
+                .expect("Error running sops")
+                .status
+                .success(),
+            "SOPS failed to stream-encrypt a large binary file"
+        );
+        // Truncate at the *segment* layer, not by lopping raw bytes off the
+        // file: chopping the container mid-document would merely fail to parse
+        // and prove nothing about the authentication. Instead parse the
+        // container, drop about one segment's worth of characters from the
+        // tail of the base64 ciphertext payload, and re-emit a still-valid
+        // document.
+        let mut encrypted = String::new();
+        File::open(&file_path)
+            .unwrap()
+            .read_to_string(&mut encrypted)
+            .unwrap();
+        let mut doc: Value =
+            serde_yaml::from_str(&encrypted).expect("streaming ciphertext is not a valid document");
+
+        // Find the longest scalar string in the document -- that is the base64
+        // ciphertext payload -- and trim a base64-aligned suffix of roughly one
+        // segment from it.
+        fn longest_scalar(v: &mut Value) -> Option<&mut String> {
+            match v {
+                Value::String(s) => Some(s),
+                Value::Mapping(m) => m
+                    .iter_mut()
+                    .filter_map(|(_, child)| longest_scalar(child))
+                    .max_by_key(|s| s.len()),
+                Value::Sequence(seq) => seq
+                    .iter_mut()
+                    .filter_map(longest_scalar)
+                    .max_by_key(|s| s.len()),
+                _ => None,
+            }
+        }
+        {
+            let payload = longest_scalar(&mut doc).expect("no ciphertext payload found");
+            // One 64 KiB plaintext segment plus its 16-byte tag, expanded by the
+            // 4/3 base64 ratio and rounded down to a 4-character boundary
+            // (87400 characters, which decode to 65550 bytes: two bytes short
+            // of a whole segment, so the last remaining segment is cut).
+            let seg_b64 = ((64 * 1024 + 16) * 4 / 3) & !3;
+            assert!(
+                payload.len() > seg_b64,
+                "payload too small to drop a whole segment"
+            );
+            payload.truncate(payload.len() - seg_b64);
+        }
+
+        // The truncated container must still parse -- otherwise we would be
+        // testing the parser, not the stream authentication.
+        let truncated = serde_yaml::to_string(&doc).expect("could not re-emit truncated container");
+        assert!(
+            serde_yaml::from_str::<Value>(&truncated).is_ok(),
+            "truncation produced an unparseable document"
+        );
+        let truncated_path = prepare_temp_file("test_stream_truncated.binary", truncated.as_bytes());
+        let output = Command::new(SOPS_BINARY_PATH)
+            .arg("decrypt")
+            .arg("--input-type")
+            .arg("binary")
+            .arg(truncated_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            !output.status.success(),
+            "SOPS decrypted a truncated streaming ciphertext"
+        );
+        let stderr = String::from_utf8_lossy(&output.stderr).to_lowercase();
+        assert!(
+            stderr.contains("authentic")
+                || stderr.contains("segment")
+                || stderr.contains("truncat")
+                || stderr.contains("mac"),
+            "failure was not attributed to the stream authentication: {}",
+            stderr
+        );
+    }
+
     #[test]
     #[ignore]
     fn roundtrip_kms_encryption_context() {
@@ -817,6 +1500,58 @@ b: ba"#
         assert!(String::from_utf8_lossy(&output.stdout).contains("baz"));
     }
 
+    #[test]
+    fn roundtrip_stdin_stdout() {
+        // With `-` (or no path) sops reads plaintext from stdin and writes
+        // ciphertext to stdout, so it can be used as a pipeline filter. Since
+        // there is no filename to sniff, the format is driven by
+        // --input-type/--output-type.
+        use std::process::Stdio;
+        let mut encrypt = Command::new(SOPS_BINARY_PATH)
+            .arg("encrypt")
+            .arg("--age")
+            .arg(AGE_RECIPIENT)
+            .arg("--input-type")
+            .arg("yaml")
+            .arg("--output-type")
+            .arg("yaml")
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Error running sops");
+        encrypt
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(b"a: secret")
+            .expect("Could not write plaintext to sops stdin");
+        let encrypted = encrypt.wait_with_output().expect("Error running sops");
+        assert!(encrypted.status.success(), "sops failed to encrypt from stdin");
+
+        let mut decrypt = Command::new(SOPS_BINARY_PATH)
+            .env("SOPS_AGE_KEY", AGE_SECRET_KEY)
+            .arg("decrypt")
+            .arg("--input-type")
+            .arg("yaml")
+            .arg("--output-type")
+            .arg("yaml")
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .expect("Error running sops");
+        decrypt
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(&encrypted.stdout)
+            .expect("Could not write ciphertext to sops stdin");
+        let decrypted = decrypt.wait_with_output().expect("Error running sops");
+        assert!(decrypted.status.success(), "sops failed to decrypt from stdin");
+        assert!(String::from_utf8_lossy(&decrypted.stdout).contains("secret"));
+    }
+
     #[test]
     fn output_flag() {
         let input_path = prepare_temp_file("test_output_flag.binary", b"foo");
@@ -841,6 +1576,79 @@ b: ba"#
         assert_ne!(contents, "", "Output file is empty");
     }
 
+    #[test]
+    fn roundtrip_via_keyservice() {
+        // A `sops keyservice` server holds the credentials and performs the
+        // data-key wrap/unwrap over a Unix socket; encrypt/decrypt route their
+        // key operations there via --keyservice instead of needing the
+        // credentials locally.
+        // Kill the server on drop so it is not leaked if an assertion below
+        // panics before we reach the explicit shutdown.
+        struct ServerGuard(std::process::Child);
+        impl Drop for ServerGuard {
+            fn drop(&mut self) {
+                let _ = self.0.kill();
+                let _ = self.0.wait();
+            }
+        }
+
+        let socket_path = Path::join(TMP_DIR.path(), "keyservice.sock");
+        let socket_arg = format!("unix://{}", socket_path.to_string_lossy());
+        let mut server = ServerGuard(
+            Command::new(SOPS_BINARY_PATH)
+                .env("SOPS_AGE_KEY", AGE_SECRET_KEY)
+                .arg("keyservice")
+                .arg("--network")
+                .arg(&socket_arg)
+                .spawn()
+                .expect("Error starting sops keyservice"),
+        );
+
+        // Wait for the server to bind the socket, but give up after a bounded
+        // number of attempts rather than spinning forever if it never starts.
+        let mut bound = false;
+        for _ in 0..100 {
+            if socket_path.exists() {
+                bound = true;
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        assert!(bound, "sops keyservice did not bind its socket in time");
+
+        let file_path =
+            prepare_temp_file("test_keyservice.yaml", "a: secret".as_bytes());
+        let encrypt = Command::new(SOPS_BINARY_PATH)
+            .arg("encrypt")
+            .arg("--age")
+            .arg(AGE_RECIPIENT)
+            .arg("--keyservice")
+            .arg(&socket_arg)
+            .arg("-i")
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        assert!(
+            encrypt.status.success(),
+            "SOPS failed to encrypt through a key service"
+        );
+
+        let decrypt = Command::new(SOPS_BINARY_PATH)
+            .env_remove("SOPS_AGE_KEY")
+            .arg("decrypt")
+            .arg("--keyservice")
+            .arg(&socket_arg)
+            .arg(file_path.clone())
+            .output()
+            .expect("Error running sops");
+        server.0.kill().expect("Could not stop sops keyservice");
+        assert!(
+            decrypt.status.success(),
+            "SOPS failed to decrypt through a key service"
+        );
+        assert!(String::from_utf8_lossy(&decrypt.stdout).contains("secret"));
+    }
+
     #[test]
     fn exec_env() {
         let file_path = prepare_temp_file(
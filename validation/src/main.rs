@@ -1,9 +1,11 @@
 use std::rc::Rc;
-use std::process::{Stdio, Command};
+use std::process::{self, Stdio, Command};
 use std::io::{Write, Read};
 use std::fs::{OpenOptions, File};
+use std::path::Path;
 use std::str;
 use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
 extern crate serde_json;
 
 use serde_json::Value;
@@ -25,6 +27,34 @@ fn run_sops_and_return_output(command: &mut Command, filename: &str) -> String {
     return String::from_utf8(output.stdout).expect("Could not decode sops's output as utf-8");
 }
 
+// Like `run_sops_and_return_output`, but hands back the raw bytes without
+// decoding them as utf-8. Every decryption, including the binary and
+// whole-file modes, uses this: plaintext can be arbitrary bytes, and decoding to `String` would
+// panic (or silently corrupt) on anything that isn't valid utf-8.
+fn run_sops_and_return_bytes(command: &mut Command, filename: &str) -> Vec<u8> {
+    let mut child = command.stdout(Stdio::piped())
+        .arg(filename)
+        .spawn()
+        .expect("Could not start sops process");
+    let output = child.wait_with_output().expect("Could not retrieve sops's output");
+    if !output.status.success() {
+        panic!("sops did not exit successfully!");
+    }
+    return output.stdout;
+}
+
+// Build a per-invocation scratch path so concurrently-running tests never share
+// the same output file. `template` carries the extension sops sniffs (e.g.
+// `temp.json`); we keep that extension but qualify the name with the process id
+// and a monotonic counter and drop it in the system temp directory.
+fn unique_temp_path(template: &str) -> String {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::SeqCst);
+    let ext = template.rsplit('.').next().unwrap_or("tmp");
+    let name = format!("sops-validate-{}-{}.{}", process::id(), n, ext);
+    env::temp_dir().join(name).to_string_lossy().into_owned()
+}
+
 fn get_sops_python() -> Command {
     let sops_python_path = env::var("SOPS_PYTHON_PATH")
         .expect("SOPS_PYTHON_PATH environment variable missing");
@@ -33,59 +63,675 @@ fn get_sops_python() -> Command {
     cmd
 }
 
+fn get_sops_rust() -> Command {
+    let sops_rust_path = env::var("SOPS_RUST_PATH")
+        .expect("SOPS_RUST_PATH environment variable missing");
+    Command::new(sops_rust_path)
+}
+
 fn encrypt_with_sops_python(plaintext: &str) -> String {
     let mut child = get_sops_python();
     let child = child.arg("-e");
     return run_sops_and_return_output(child, plaintext);
 }
 
-fn decrypt_with_sops_python(ciphertext: &str) -> String {
+fn decrypt_with_sops_python(ciphertext: &str) -> Vec<u8> {
     let mut child = get_sops_python();
     let child = child.arg("-d");
-    return run_sops_and_return_output(child, ciphertext);
+    return run_sops_and_return_bytes(child, ciphertext);
 }
 
-fn validate_json_file(input_file_name: &str,
-                      encrypt: fn(&str) -> String,
-                      decrypt: fn(&str) -> String) {
-    let output_file_name = "temp.json";
-    let mut input = String::new();
-    File::open(input_file_name).unwrap().read_to_string(&mut input);
-    let input_value: Value = serde_json::from_str(&input).expect("Could not decode input json");
+fn encrypt_with_sops_rust(plaintext: &str) -> String {
+    let mut child = get_sops_rust();
+    let child = child.arg("-e");
+    return run_sops_and_return_output(child, plaintext);
+}
+
+fn decrypt_with_sops_rust(ciphertext: &str) -> Vec<u8> {
+    let mut child = get_sops_rust();
+    let child = child.arg("-d");
+    return run_sops_and_return_bytes(child, ciphertext);
+}
+
+// Whole-file mode encrypts the complete document as one AES-GCM blob emitted as
+// a single base64 string, instead of walking the tree and encrypting each leaf.
+// It is the only correct behaviour for inputs the tree loaders can't represent
+// losslessly: comment-heavy YAML, exotic JSON number formatting, or truly binary
+// data.
+fn encrypt_with_sops_rust_whole_file(plaintext: &str) -> String {
+    let mut child = get_sops_rust();
+    let child = child.arg("-e").arg("--whole-file");
+    return run_sops_and_return_output(child, plaintext);
+}
+
+fn decrypt_with_sops_rust_whole_file(ciphertext: &str) -> Vec<u8> {
+    let mut child = get_sops_rust();
+    let child = child.arg("-d").arg("--whole-file");
+    return run_sops_and_return_bytes(child, ciphertext);
+}
+
+// Decryption normally re-unwraps the file data key on every invocation. With
+// the keyring cache enabled, the decrypted data key is stored in the OS keyring
+// (Secret Service / macOS Keychain / Windows Credential Manager) under a service
+// name derived from the file path plus a hash of the encrypted metadata, so an
+// edited/re-encrypted file invalidates the old entry automatically. A keyring
+// that is unavailable degrades gracefully to the uncached behaviour.
+fn decrypt_with_sops_rust_cached(ciphertext: &str) -> Vec<u8> {
+    // `--cache` enables the keyring lookup/store path; without it the decrypt is
+    // identical to the uncached variant, so the flag is what makes this test
+    // actually exercise caching.
+    let mut child = get_sops_rust();
+    let child = child.arg("-d").arg("--cache");
+    return run_sops_and_return_bytes(child, ciphertext);
+}
+
+// `--no-cache` bypasses the keyring entirely, forcing a full key-group unwrap.
+fn decrypt_with_sops_rust_no_cache(ciphertext: &str) -> Vec<u8> {
+    let mut child = get_sops_rust();
+    let child = child.arg("-d").arg("--no-cache");
+    return run_sops_and_return_bytes(child, ciphertext);
+}
+
+// Drop any cached data key for `file_name` from the OS keyring.
+fn purge_rust_cache(file_name: &str) {
+    let status = get_sops_rust()
+        .arg("purge-cache")
+        .arg(file_name)
+        .status()
+        .expect("Could not run sops purge-cache");
+    if !status.success() {
+        panic!("sops purge-cache did not exit successfully");
+    }
+}
+
+// age recipient (an `age1...` bech32 X25519 public key) and identity
+// (`AGE-SECRET-KEY-...`) used to exercise the native Rust key-management
+// backend. They are read from the environment so the same keypair can be shared
+// with the command-line tool; the caller is expected to have generated them
+// with `age-keygen`.
+fn age_recipient() -> String {
+    env::var("SOPS_AGE_RECIPIENT").expect("SOPS_AGE_RECIPIENT environment variable missing")
+}
+
+// The matching `AGE-SECRET-KEY-...` identity. We pass it explicitly on decrypt
+// rather than relying on whatever `SOPS_AGE_KEY` happens to be set in the
+// caller's environment, so the round trip is self-contained.
+fn age_identity() -> String {
+    env::var("SOPS_AGE_SECRET_KEY").expect("SOPS_AGE_SECRET_KEY environment variable missing")
+}
+
+fn encrypt_with_sops_rust_age(plaintext: &str) -> String {
+    let mut child = get_sops_rust();
+    let child = child.arg("-e").arg("--age").arg(age_recipient());
+    return run_sops_and_return_output(child, plaintext);
+}
+
+fn decrypt_with_sops_rust_age(ciphertext: &str) -> Vec<u8> {
+    // Wire the identity explicitly via SOPS_AGE_KEY so decryption doesn't depend
+    // on an ambient environment variable; the backend tries the identity against
+    // every `age` entry until one unwraps the data key.
+    let mut child = get_sops_rust();
+    let child = child.env("SOPS_AGE_KEY", age_identity()).arg("-d");
+    return run_sops_and_return_bytes(child, ciphertext);
+}
+
+// Decrypt `file_name` to a temporary directory, open it in `$EDITOR`, and
+// re-encrypt on save. The temp file keeps the original file name (we create a
+// fresh directory and join the source's `file_name()` inside it rather than
+// using a random temp name) so the editor's title bar shows the real file and
+// the format loader still recognises the extension.
+fn edit_file(file_name: &str) {
+    let path = Path::new(file_name);
+    let base = path.file_name().expect("Source path has no file name");
+
+    let dir = env::temp_dir().join(format!("sops-edit-{}", process::id()));
+    std::fs::create_dir_all(&dir).expect("Could not create temporary directory");
+    let temp_path = dir.join(base);
+    let temp_name = temp_path.to_str().expect("Temporary path is not valid utf-8").to_owned();
+
+    // Clean up the temp dir on every exit path, success or panic.
+    let _guard = TempDirGuard(dir.clone());
+
+    let plaintext = String::from_utf8(decrypt_with_sops_rust(file_name))
+        .expect("Decrypted plaintext is not valid utf-8");
+    {
+        let mut temp_file = File::create(&temp_path).expect("Could not create temporary file");
+        temp_file.write_all(plaintext.as_bytes()).expect("Could not write temporary file");
+    }
+
+    let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".to_owned());
+    let status = Command::new(&editor)
+        .arg(&temp_name)
+        .status()
+        .expect("Could not launch editor");
+    if !status.success() {
+        panic!("Editor did not exit successfully");
+    }
+
+    let mut edited = String::new();
+    File::open(&temp_path).expect("Could not reopen temporary file")
+        .read_to_string(&mut edited)
+        .expect("Could not read edited file");
+
+    // Abort without writing if the plaintext is unchanged or no longer parses.
+    if edited == plaintext {
+        return;
+    }
+    if !plaintext_parses(&temp_name, &edited) {
+        panic!("Edited file did not parse; leaving {} untouched", file_name);
+    }
+
+    // Re-encrypt only the leaf values that changed, against the existing key
+    // groups. We drive this through `sops set` on the *original* encrypted file
+    // so its data key and recipients are reused (and the MAC recomputed) rather
+    // than shelling out to `sops -e`, which would mint a fresh data key and key
+    // set and drop the file's existing recipients.
+    let old_value = plaintext_to_value(&temp_name, &plaintext);
+    let new_value = plaintext_to_value(&temp_name, &edited);
+    let mut sets = Vec::new();
+    let mut removals = Vec::new();
+    let mut path = Vec::new();
+    diff_leaves(Some(&old_value), &new_value, &mut path, &mut sets, &mut removals);
+    for &(ref sops_path, ref value) in &sets {
+        let status = get_sops_rust()
+            .arg("set")
+            .arg(file_name)
+            .arg(sops_path)
+            .arg(value)
+            .status()
+            .expect("Could not run sops set");
+        if !status.success() {
+            panic!("sops set did not exit successfully");
+        }
+    }
+    for sops_path in &removals {
+        let status = get_sops_rust()
+            .arg("unset")
+            .arg(file_name)
+            .arg(sops_path)
+            .status()
+            .expect("Could not run sops unset");
+        if !status.success() {
+            panic!("sops unset did not exit successfully");
+        }
+    }
+}
+
+// A path into a document, used to address a leaf for `sops set`. Only object
+// keys are addressed individually; arrays and other composites are re-set as a
+// whole value at their parent key.
+enum PathSeg {
+    Key(String),
+}
+
+// Render a path as the bracketed form `sops set` expects, e.g. `["a"]["b"]`.
+fn sops_path(path: &[PathSeg]) -> String {
+    let mut out = String::new();
+    for seg in path {
+        match *seg {
+            PathSeg::Key(ref k) => {
+                // `to_string` of a string yields a quoted `"a"`; wrap that in
+                // brackets to get `["a"]`.
+                let encoded = serde_json::to_string(k).expect("Could not encode key");
+                out.push('[');
+                out.push_str(&encoded);
+                out.push(']');
+            }
+        }
+    }
+    out
+}
+
+// Walk `old`/`new` in parallel and record a `(path, json value)` pair for every
+// leaf that was added or changed, recursing into objects so only the differing
+// leaves are re-encrypted.
+fn diff_leaves(old: Option<&Value>,
+               new: &Value,
+               path: &mut Vec<PathSeg>,
+               sets: &mut Vec<(String, String)>,
+               removals: &mut Vec<String>) {
+    match (old, new) {
+        (Some(&Value::Object(ref o)), &Value::Object(ref n)) => {
+            for (k, v) in n {
+                path.push(PathSeg::Key(k.clone()));
+                diff_leaves(o.get(k), v, path, sets, removals);
+                path.pop();
+            }
+            // Keys present in the old document but gone from the edit must be
+            // unset, otherwise the edit workflow silently cannot delete leaves.
+            for k in o.keys() {
+                if !n.contains_key(k) {
+                    path.push(PathSeg::Key(k.clone()));
+                    removals.push(sops_path(path));
+                    path.pop();
+                }
+            }
+        }
+        _ => {
+            if old != Some(new) {
+                let value = serde_json::to_string(new).expect("Could not encode value");
+                sets.push((sops_path(path), value));
+            }
+        }
+    }
+}
+
+// Parse edited plaintext into a `serde_json::Value` regardless of its on-disk
+// format, so the leaf diff can be computed uniformly.
+fn plaintext_to_value(file_name: &str, contents: &str) -> Value {
+    if file_name.ends_with(".json") {
+        serde_json::from_str(contents).expect("Could not decode json")
+    } else if file_name.ends_with(".yaml") || file_name.ends_with(".yml") {
+        let docs = YamlLoader::load_from_str(contents).expect("Could not decode yaml");
+        let first = docs.first().cloned().unwrap_or(yaml_rust::Yaml::Null);
+        tree_to_json(&yaml_to_tree(&first))
+    } else {
+        let mut map = serde_json::Map::new();
+        map.insert("data".to_owned(), Value::String(contents.to_owned()));
+        Value::Object(map)
+    }
+}
+
+// Reparse the edited plaintext with the same format loader used elsewhere in
+// this chunk, selected by the file extension.
+fn plaintext_parses(file_name: &str, contents: &str) -> bool {
+    if file_name.ends_with(".json") {
+        serde_json::from_str::<Value>(contents).is_ok()
+    } else if file_name.ends_with(".yaml") || file_name.ends_with(".yml") {
+        YamlLoader::load_from_str(contents).is_ok()
+    } else {
+        true
+    }
+}
+
+struct TempDirGuard(std::path::PathBuf);
+
+impl Drop for TempDirGuard {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+// A format-agnostic view of a decrypted document. Every `Store` lowers its
+// native representation onto this shared tree so the interop harness can compare
+// inputs and outputs without caring which format produced them.
+#[derive(Debug, PartialEq, Clone)]
+enum Tree {
+    Map(Vec<(String, Tree)>),
+    Sequence(Vec<Tree>),
+    Scalar(String),
+}
+
+// A pluggable document format. `load_plain` parses raw plaintext into the shared
+// tree and `emit_plain` renders it back out; `temp_file_name` gives the scratch
+// file an extension sops can sniff.
+trait Store {
+    fn load_plain(&self, data: &[u8]) -> Tree;
+    fn emit_plain(&self, tree: &Tree) -> Vec<u8>;
+    fn temp_file_name(&self) -> &str;
+}
+
+struct JsonStore;
+struct YamlStore;
+struct DotenvStore;
+struct IniStore;
+struct BinaryStore;
+
+fn json_to_tree(value: &Value) -> Tree {
+    match *value {
+        Value::Object(ref map) => {
+            Tree::Map(map.iter().map(|(k, v)| (k.clone(), json_to_tree(v))).collect())
+        }
+        Value::Array(ref items) => Tree::Sequence(items.iter().map(json_to_tree).collect()),
+        Value::String(ref s) => Tree::Scalar(s.clone()),
+        Value::Null => Tree::Scalar(String::new()),
+        ref other => Tree::Scalar(other.to_string()),
+    }
+}
+
+fn tree_to_json(tree: &Tree) -> Value {
+    match *tree {
+        Tree::Map(ref entries) => {
+            Value::Object(entries.iter().map(|&(ref k, ref v)| (k.clone(), tree_to_json(v))).collect())
+        }
+        Tree::Sequence(ref items) => Value::Array(items.iter().map(tree_to_json).collect()),
+        Tree::Scalar(ref s) => Value::String(s.clone()),
+    }
+}
+
+fn yaml_to_tree(value: &yaml_rust::Yaml) -> Tree {
+    use yaml_rust::Yaml;
+    match *value {
+        Yaml::Hash(ref hash) => Tree::Map(hash.iter()
+            .map(|(k, v)| (yaml_scalar(k), yaml_to_tree(v)))
+            .collect()),
+        Yaml::Array(ref items) => Tree::Sequence(items.iter().map(yaml_to_tree).collect()),
+        ref scalar => Tree::Scalar(yaml_scalar(scalar)),
+    }
+}
+
+fn yaml_scalar(value: &yaml_rust::Yaml) -> String {
+    use yaml_rust::Yaml;
+    match *value {
+        Yaml::String(ref s) => s.clone(),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Real(ref r) => r.clone(),
+        Yaml::Boolean(b) => b.to_string(),
+        Yaml::Null => String::new(),
+        _ => String::new(),
+    }
+}
+
+impl Store for JsonStore {
+    fn load_plain(&self, data: &[u8]) -> Tree {
+        let value: Value = serde_json::from_slice(data).expect("Could not decode json");
+        json_to_tree(&value)
+    }
+    fn emit_plain(&self, tree: &Tree) -> Vec<u8> {
+        serde_json::to_vec(&tree_to_json(tree)).expect("Could not encode json")
+    }
+    fn temp_file_name(&self) -> &str { "temp.json" }
+}
+
+impl Store for YamlStore {
+    fn load_plain(&self, data: &[u8]) -> Tree {
+        let text = str::from_utf8(data).expect("yaml is not valid utf-8");
+        let docs = YamlLoader::load_from_str(text).expect("Could not decode yaml");
+        Tree::Sequence(docs.iter().map(yaml_to_tree).collect())
+    }
+    fn emit_plain(&self, tree: &Tree) -> Vec<u8> {
+        // Emit each document in the sequence back out through the yaml writer.
+        let mut out = String::new();
+        if let Tree::Sequence(ref docs) = *tree {
+            for doc in docs {
+                let mut emitter = YamlEmitter::new(&mut out);
+                emitter.dump(&tree_to_yaml(doc)).expect("Could not encode yaml");
+            }
+        }
+        out.into_bytes()
+    }
+    fn temp_file_name(&self) -> &str { "temp.yaml" }
+}
+
+fn tree_to_yaml(tree: &Tree) -> yaml_rust::Yaml {
+    use yaml_rust::Yaml;
+    use yaml_rust::yaml::Hash;
+    match *tree {
+        Tree::Map(ref entries) => {
+            let mut hash = Hash::new();
+            for &(ref k, ref v) in entries {
+                hash.insert(Yaml::String(k.clone()), tree_to_yaml(v));
+            }
+            Yaml::Hash(hash)
+        }
+        Tree::Sequence(ref items) => Yaml::Array(items.iter().map(tree_to_yaml).collect()),
+        Tree::Scalar(ref s) => Yaml::String(s.clone()),
+    }
+}
+
+impl Store for DotenvStore {
+    fn load_plain(&self, data: &[u8]) -> Tree {
+        let text = str::from_utf8(data).expect("dotenv is not valid utf-8");
+        let mut entries = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(eq) = line.find('=') {
+                let (key, value) = line.split_at(eq);
+                entries.push((key.to_owned(), Tree::Scalar(value[1..].to_owned())));
+            }
+        }
+        Tree::Map(entries)
+    }
+    fn emit_plain(&self, tree: &Tree) -> Vec<u8> {
+        let mut out = String::new();
+        if let Tree::Map(ref entries) = *tree {
+            for &(ref k, ref v) in entries {
+                if let Tree::Scalar(ref value) = *v {
+                    out.push_str(k);
+                    out.push('=');
+                    out.push_str(value);
+                    out.push('\n');
+                }
+            }
+        }
+        out.into_bytes()
+    }
+    fn temp_file_name(&self) -> &str { "temp.env" }
+}
+
+impl Store for IniStore {
+    fn load_plain(&self, data: &[u8]) -> Tree {
+        let text = str::from_utf8(data).expect("ini is not valid utf-8");
+        let mut sections: Vec<(String, Tree)> = Vec::new();
+        let mut current: Vec<(String, Tree)> = Vec::new();
+        let mut section_name = String::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                sections.push((section_name.clone(), Tree::Map(current.clone())));
+                current.clear();
+                section_name = line[1..line.len() - 1].to_owned();
+            } else if let Some(eq) = line.find('=') {
+                let (key, value) = line.split_at(eq);
+                current.push((key.trim().to_owned(), Tree::Scalar(value[1..].trim().to_owned())));
+            }
+        }
+        sections.push((section_name, Tree::Map(current)));
+        Tree::Map(sections)
+    }
+    fn emit_plain(&self, tree: &Tree) -> Vec<u8> {
+        let mut out = String::new();
+        if let Tree::Map(ref sections) = *tree {
+            for &(ref name, ref body) in sections {
+                if !name.is_empty() {
+                    out.push('[');
+                    out.push_str(name);
+                    out.push_str("]\n");
+                }
+                if let Tree::Map(ref entries) = *body {
+                    for &(ref k, ref v) in entries {
+                        if let Tree::Scalar(ref value) = *v {
+                            out.push_str(k);
+                            out.push('=');
+                            out.push_str(value);
+                            out.push('\n');
+                        }
+                    }
+                }
+            }
+        }
+        out.into_bytes()
+    }
+    fn temp_file_name(&self) -> &str { "temp.ini" }
+}
+
+impl Store for BinaryStore {
+    // Files that don't parse as a structured tree are wrapped as a single `data`
+    // leaf so arbitrary blobs can still be encrypted. The bytes are base64-encoded
+    // rather than decoded as utf-8, so non-utf-8 input survives losslessly
+    // instead of being mangled by replacement characters.
+    fn load_plain(&self, data: &[u8]) -> Tree {
+        Tree::Map(vec![("data".to_owned(), Tree::Scalar(base64_encode(data)))])
+    }
+    fn emit_plain(&self, tree: &Tree) -> Vec<u8> {
+        if let Tree::Map(ref entries) = *tree {
+            if let Some(&(_, Tree::Scalar(ref data))) = entries.first() {
+                return base64_decode(data);
+            }
+        }
+        Vec::new()
+    }
+    fn temp_file_name(&self) -> &str { "temp.bin" }
+}
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in data.chunks(3) {
+        let b = [chunk[0],
+                 *chunk.get(1).unwrap_or(&0),
+                 *chunk.get(2).unwrap_or(&0)];
+        let n = ((b[0] as u32) << 16) | ((b[1] as u32) << 8) | (b[2] as u32);
+        out.push(BASE64_ALPHABET[((n >> 18) & 0x3f) as usize] as char);
+        out.push(BASE64_ALPHABET[((n >> 12) & 0x3f) as usize] as char);
+        out.push(if chunk.len() > 1 {
+            BASE64_ALPHABET[((n >> 6) & 0x3f) as usize] as char
+        } else { '=' });
+        out.push(if chunk.len() > 2 {
+            BASE64_ALPHABET[(n & 0x3f) as usize] as char
+        } else { '=' });
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Vec<u8> {
+    fn val(c: u8) -> Option<u32> {
+        BASE64_ALPHABET.iter().position(|&a| a == c).map(|p| p as u32)
+    }
+    let mut out = Vec::new();
+    let mut buf = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        if c == b'=' { break; }
+        let v = match val(c) {
+            Some(v) => v,
+            None => continue, // skip whitespace/newlines
+        };
+        buf = (buf << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+        }
+    }
+    out
+}
+
+// The single validator every format flows through: parse the input with the
+// store, round-trip it through encrypt/decrypt, and assert the decrypted tree
+// matches.
+fn validate_file<S: Store>(store: &S,
+                           input_file_name: &str,
+                           encrypt: fn(&str) -> String,
+                           decrypt: fn(&str) -> Vec<u8>) {
+    let mut input = Vec::new();
+    File::open(input_file_name).unwrap().read_to_end(&mut input).unwrap();
+    let input_tree = store.load_plain(&input);
+
+    // Exercise the store's emitter: a tree rendered back out through
+    // `emit_plain` must re-parse to the same tree, keeping emit and load in
+    // step.
+    assert_eq!(input_tree, store.load_plain(&store.emit_plain(&input_tree)));
+
+    let output_file_name = unique_temp_path(store.temp_file_name());
     let encrypted_output = encrypt(input_file_name);
     let mut output_file = OpenOptions::new()
         .write(true)
         .create(true)
-        .open(output_file_name)
+        .open(&output_file_name)
         .expect("Could not open output file");
     output_file.write_all(encrypted_output.as_bytes()).expect("Could not write to output file");
-    let decryption = decrypt(output_file_name);
-    let output_value: Value = serde_json::from_str(&decryption).unwrap();
-    std::fs::remove_file(output_file_name).expect("Could not remove output file");
-    assert_eq!(input_value, output_value);
+
+    let decryption = decrypt(&output_file_name);
+    let output_tree = store.load_plain(&decryption);
+    std::fs::remove_file(&output_file_name).expect("Could not remove output file");
+    assert_eq!(input_tree, output_tree);
+}
+
+// Whole-file validation asserts byte-for-byte round-trip equality rather than
+// `Value`/`Tree` equality, since the point of the mode is preserving the
+// original bytes exactly.
+fn validate_whole_file(input_file_name: &str,
+                       encrypt: fn(&str) -> String,
+                       decrypt: fn(&str) -> Vec<u8>) {
+    let mut input = Vec::new();
+    File::open(input_file_name).unwrap().read_to_end(&mut input).unwrap();
+
+    let output_file_name = unique_temp_path("temp.whole");
+    let encrypted_output = encrypt(input_file_name);
+    let mut output_file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .open(&output_file_name)
+        .expect("Could not open output file");
+    output_file.write_all(encrypted_output.as_bytes()).expect("Could not write to output file");
+
+    let decryption = decrypt(&output_file_name);
+    std::fs::remove_file(&output_file_name).expect("Could not remove output file");
+    assert_eq!(input, decryption);
+}
+
+fn validate_json_file(input_file_name: &str,
+                      encrypt: fn(&str) -> String,
+                      decrypt: fn(&str) -> Vec<u8>) {
+    validate_file(&JsonStore, input_file_name, encrypt, decrypt);
 }
 
 fn validate_yaml_file(input_file_name: &str,
                       encrypt: fn(&str) -> String,
-                      decrypt: fn(&str) -> String) {
-    let output_file_name = "temp.yaml";
-    let mut input = String::new();
-    File::open(input_file_name).unwrap().read_to_string(&mut input);
-    let input_value = YamlLoader::load_from_str(&input).expect("Could not decode input yaml");
+                      decrypt: fn(&str) -> Vec<u8>) {
+    validate_file(&YamlStore, input_file_name, encrypt, decrypt);
+}
+
+// Type-preserving validator for the cross-implementation cases. The shared
+// `Tree` stringifies every scalar (`2` -> `"2"`, `true` -> `"true"`), so a
+// comparison through it would silently accept a Python-emitted integer against
+// a Rust-emitted string. These mixed py<->rust round trips exist precisely to
+// catch type and number-format divergences, so they compare on the native
+// parse (`serde_json::Value` / `yaml_rust::Yaml`) instead.
+fn validate_typed<T: PartialEq + std::fmt::Debug>(parse: fn(&[u8]) -> T,
+                                                   temp_name: &str,
+                                                   input_file_name: &str,
+                                                   encrypt: fn(&str) -> String,
+                                                   decrypt: fn(&str) -> Vec<u8>) {
+    let mut input = Vec::new();
+    File::open(input_file_name).unwrap().read_to_end(&mut input).unwrap();
+    let input_value = parse(&input);
+
+    let output_file_name = unique_temp_path(temp_name);
     let encrypted_output = encrypt(input_file_name);
     let mut output_file = OpenOptions::new()
         .write(true)
         .create(true)
-        .open(output_file_name)
+        .open(&output_file_name)
         .expect("Could not open output file");
     output_file.write_all(encrypted_output.as_bytes()).expect("Could not write to output file");
-    let decryption = decrypt(output_file_name);
-    let output_value = YamlLoader::load_from_str(&decryption)
-        .expect("Could not decode output yaml");
-    std::fs::remove_file(output_file_name).expect("Could not remove output file");
+
+    let decryption = decrypt(&output_file_name);
+    let output_value = parse(&decryption);
+    std::fs::remove_file(&output_file_name).expect("Could not remove output file");
     assert_eq!(input_value, output_value);
 }
 
+fn parse_json_value(data: &[u8]) -> Value {
+    serde_json::from_slice(data).expect("Could not decode json")
+}
+
+fn parse_yaml_value(data: &[u8]) -> Vec<yaml_rust::Yaml> {
+    let text = str::from_utf8(data).expect("yaml is not valid utf-8");
+    YamlLoader::load_from_str(text).expect("Could not decode yaml")
+}
+
+fn validate_json_file_typed(input_file_name: &str,
+                            encrypt: fn(&str) -> String,
+                            decrypt: fn(&str) -> Vec<u8>) {
+    validate_typed(parse_json_value, "temp.json", input_file_name, encrypt, decrypt);
+}
+
+fn validate_yaml_file_typed(input_file_name: &str,
+                            encrypt: fn(&str) -> String,
+                            decrypt: fn(&str) -> Vec<u8>) {
+    validate_typed(parse_yaml_value, "temp.yaml", input_file_name, encrypt, decrypt);
+}
+
 #[test]
 fn validate_python_json() {
     validate_json_file("example.json",
@@ -100,3 +746,167 @@ fn validate_python_yaml() {
                        encrypt_with_sops_python,
                        decrypt_with_sops_python);
 }
+
+#[test]
+fn validate_rust_json() {
+    validate_json_file("example.json",
+                       encrypt_with_sops_rust,
+                       decrypt_with_sops_rust);
+}
+
+#[test]
+fn validate_rust_yaml() {
+    validate_yaml_file("example.yaml",
+                       encrypt_with_sops_rust,
+                       decrypt_with_sops_rust);
+}
+
+// Mixed cases: a file encrypted by one implementation must decrypt cleanly with
+// the other. These are the combinations that actually prove file-format
+// compatibility -- same-implementation round trips hide metadata, MAC and
+// tree-ordering divergences because both ends share the same bug.
+#[test]
+fn validate_python_to_rust_json() {
+    validate_json_file_typed("example.json",
+                             encrypt_with_sops_python,
+                             decrypt_with_sops_rust);
+}
+
+#[test]
+fn validate_rust_to_python_json() {
+    validate_json_file_typed("example.json",
+                             encrypt_with_sops_rust,
+                             decrypt_with_sops_python);
+}
+
+#[test]
+fn validate_python_to_rust_yaml() {
+    validate_yaml_file_typed("example.yaml",
+                             encrypt_with_sops_python,
+                             decrypt_with_sops_rust);
+}
+
+#[test]
+fn validate_rust_to_python_yaml() {
+    validate_yaml_file_typed("example.yaml",
+                             encrypt_with_sops_rust,
+                             decrypt_with_sops_python);
+}
+
+// The age backend wraps the file data key without PGP or a cloud KMS, so these
+// round trips run entirely inside the Rust port.
+#[test]
+fn validate_rust_age_json() {
+    validate_json_file("example.json",
+                       encrypt_with_sops_rust_age,
+                       decrypt_with_sops_rust_age);
+}
+
+#[test]
+fn validate_rust_age_yaml() {
+    validate_yaml_file("example.yaml",
+                       encrypt_with_sops_rust_age,
+                       decrypt_with_sops_rust_age);
+}
+
+#[test]
+fn validate_rust_dotenv() {
+    validate_file(&DotenvStore, "example.env",
+                  encrypt_with_sops_rust,
+                  decrypt_with_sops_rust);
+}
+
+#[test]
+fn validate_rust_ini() {
+    validate_file(&IniStore, "example.ini",
+                  encrypt_with_sops_rust,
+                  decrypt_with_sops_rust);
+}
+
+#[test]
+fn validate_rust_binary() {
+    validate_file(&BinaryStore, "example.bin",
+                  encrypt_with_sops_rust,
+                  decrypt_with_sops_rust);
+}
+
+#[test]
+fn validate_rust_whole_file() {
+    validate_whole_file("example.bin",
+                        encrypt_with_sops_rust_whole_file,
+                        decrypt_with_sops_rust_whole_file);
+}
+
+// A cached decrypt must actually read a data key back out of the keyring and
+// still produce the correct plaintext. Because the cache is keyed on file path
+// plus metadata hash, a hit is only possible when the *same* encrypted file is
+// decrypted twice: the first `--cache` decrypt misses and writes the data key
+// back, the second hits it. Both must agree with an independent uncached decrypt
+// and with each other -- the keyring is a transparent accelerator, never a
+// source of different plaintext.
+#[test]
+fn validate_rust_json_cached() {
+    let encrypted_file = unique_temp_path("temp.json");
+    let encrypted_output = encrypt_with_sops_rust("example.json");
+    {
+        let mut output_file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .open(&encrypted_file)
+            .expect("Could not open output file");
+        output_file.write_all(encrypted_output.as_bytes()).expect("Could not write to output file");
+    }
+
+    // Start from a known-cold cache for this file.
+    purge_rust_cache(&encrypted_file);
+
+    let uncached = parse_json_value(&decrypt_with_sops_rust_no_cache(&encrypted_file));
+    // First cached decrypt: miss, unwrap, write the data key back to the keyring.
+    let first = parse_json_value(&decrypt_with_sops_rust_cached(&encrypted_file));
+    // Second cached decrypt of the *same* file: this is the hit we care about.
+    let second = parse_json_value(&decrypt_with_sops_rust_cached(&encrypted_file));
+
+    purge_rust_cache(&encrypted_file);
+    std::fs::remove_file(&encrypted_file).expect("Could not remove output file");
+
+    assert_eq!(uncached, first);
+    assert_eq!(first, second);
+}
+
+// Exercise the full edit workflow: encrypt a fixture, change a leaf through a
+// scripted $EDITOR, and confirm the edit round-trips. Because re-encryption runs
+// as `sops set` against the original file, the file keeps its existing key
+// groups rather than getting a freshly generated data key.
+#[test]
+fn edit_file_changes_leaf() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let src = unique_temp_path("edit.json");
+    let ciphertext = encrypt_with_sops_rust("example.json");
+    {
+        let mut f = File::create(&src).expect("Could not create encrypted fixture");
+        f.write_all(ciphertext.as_bytes()).expect("Could not write encrypted fixture");
+    }
+
+    // A scripted editor that overwrites the decrypted temp file with a changed
+    // value; it receives the temp file path as its single argument.
+    let editor_path = unique_temp_path("editor.sh");
+    {
+        let mut f = File::create(&editor_path).expect("Could not create editor script");
+        f.write_all(b"#!/bin/sh\ncat > \"$1\" <<'EOF'\n{\"inserted\": \"edited-value\"}\nEOF\n")
+            .expect("Could not write editor script");
+        let mut perms = f.metadata().unwrap().permissions();
+        perms.set_mode(0o755);
+        std::fs::set_permissions(&editor_path, perms).expect("Could not chmod editor script");
+    }
+    env::set_var("EDITOR", &editor_path);
+
+    edit_file(&src);
+
+    let plaintext = String::from_utf8(decrypt_with_sops_rust(&src))
+        .expect("Decrypted plaintext is not valid utf-8");
+    assert!(plaintext.contains("edited-value"), "edit did not round-trip");
+
+    std::fs::remove_file(&src).ok();
+    std::fs::remove_file(&editor_path).ok();
+}